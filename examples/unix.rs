@@ -11,9 +11,9 @@ struct Opt {
     /// Log level
     #[structopt(short = "d", long = "loglevel", default_value = "info")]
     loglevel: String,
-    /// Clock speed in Hz
-    #[structopt(short = "c", long = "clock", default_value = "1000")]
-    hz: u64,
+    /// Instructions to execute per 60 Hz frame
+    #[structopt(short = "c", long = "cycles", default_value = "10")]
+    cycles_per_frame: usize,
 }
 
 struct Hardware {
@@ -85,8 +85,8 @@ impl libchip8::Hardware for Hardware {
 
         let win = match Window::new(
             "Chip8",
-            64,
-            32,
+            size.0,
+            size.1,
             WindowOptions {
                 resize: true,
                 scale: Scale::X4,
@@ -113,10 +113,18 @@ impl libchip8::Hardware for Hardware {
             .wrapping_add(d.subsec_nanos().into())
     }
 
-    fn beep(&mut self) {}
+    fn sound_on(&mut self) {
+        info!("buzzer on");
+    }
+
+    fn sound_off(&mut self) {
+        info!("buzzer off");
+    }
 
     fn sched(&mut self) -> bool {
-        std::thread::sleep(std::time::Duration::from_micros(1000_000 / self.opt.hz));
+        // `sched` is now called once per frame instead of once per instruction,
+        // so pace the loop at 60 Hz here rather than per-instruction.
+        std::thread::sleep(std::time::Duration::from_micros(1000_000 / 60));
 
         if let Some(win) = &mut self.win {
             if !win.is_open() || win.is_key_down(Key::Escape) {
@@ -129,7 +137,8 @@ impl libchip8::Hardware for Hardware {
                 .into_iter()
                 .map(|b| if b { 0xffffff } else { 0 })
                 .collect();
-            win.update_with_buffer(&vram).unwrap();
+            win.update_with_buffer(&vram, self.vramsz.0, self.vramsz.1)
+                .unwrap();
         }
 
         false
@@ -161,6 +170,9 @@ fn main() {
         log4rs::init_config(config).unwrap();
     }
 
-    let chip8 = libchip8::Chip8::new(Hardware::new(opt));
+    let cycles_per_frame = opt.cycles_per_frame;
+
+    let mut chip8 = libchip8::Chip8::new(Hardware::new(opt));
+    chip8.set_cycles_per_frame(cycles_per_frame);
     chip8.run(include_bytes!("roms/invaders.ch8"));
 }
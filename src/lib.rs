@@ -20,6 +20,10 @@
 
 #![no_std]
 
+#[cfg(test)]
+extern crate std;
+
+use core::fmt;
 use log::*;
 
 /// Represents environment-specific logic.
@@ -41,7 +45,8 @@ pub trait Hardware: Sized {
     /// Get the current state of a pixel in the screen.
     fn vram_get(&mut self, x: usize, y: usize) -> bool;
 
-    /// Set the size of the screen.
+    /// Set the size of the screen. May be called more than once during
+    /// execution, e.g. when a SCHIP ROM switches between low- and high-res.
     fn vram_setsize(&mut self, size: (usize, usize));
 
     /// Get the size of the screen.
@@ -50,8 +55,11 @@ pub trait Hardware: Sized {
     /// Return the current clock value in nanoseconds.
     fn clock(&mut self) -> u64;
 
-    /// Play beep sound.
-    fn beep(&mut self);
+    /// Called once when the sound timer becomes non-zero; start the buzzer tone.
+    fn sound_on(&mut self) {}
+
+    /// Called once when the sound timer reaches zero; stop the buzzer tone.
+    fn sound_off(&mut self) {}
 
     /// Called in every step; return `true` for shutdown.
     fn sched(&mut self) -> bool {
@@ -59,6 +67,44 @@ pub trait Hardware: Sized {
     }
 }
 
+/// Configurable behavior for opcodes whose semantics differ across CHIP-8
+/// interpreters.
+///
+/// Different ROMs were written against different interpreters, and assume
+/// whichever one of these behaviors their author tested against. The defaults
+/// match this crate's historical behavior; flip individual fields to match the
+/// ROM being run, e.g. to pass the community CHIP-8 test ROM suites.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Quirks {
+    /// `8xy6`/`8xyE`: shift `Vx` in place (`true`, default) instead of first
+    /// copying `Vy` into `Vx` and shifting that (`false`).
+    pub shift_in_place: bool,
+    /// `Fx55`/`Fx65`: leave `I` unchanged (`false`, default) instead of
+    /// incrementing it by `x + 1` afterwards (`true`).
+    pub load_store_increment: bool,
+    /// `Bnnn`: jump to `nnn + V0` (`false`, default) instead of `nnn + Vx`,
+    /// where `x` is the top nibble of `nnn` (`true`).
+    pub jump_vx: bool,
+    /// `8xy1`/`8xy2`/`8xy3`: leave `VF` unchanged (`false`, default) instead of
+    /// clearing it afterwards (`true`).
+    pub vf_reset: bool,
+    /// `Dxyn`/`Dxy0`: draw immediately (`false`, default) instead of blocking
+    /// until the next 60 Hz tick (`true`).
+    pub display_wait: bool,
+}
+
+impl Default for Quirks {
+    fn default() -> Self {
+        Self {
+            shift_in_place: true,
+            load_store_increment: false,
+            jump_vx: false,
+            vf_reset: false,
+            display_wait: false,
+        }
+    }
+}
+
 /// Interpreter instance
 pub struct Chip8<T> {
     v: [u8; REGS],
@@ -71,6 +117,13 @@ pub struct Chip8<T> {
     stack: [u16; STACKS],
     time: Option<u64>,
     running: bool,
+    breakpoints: [Option<u16>; BREAKPOINTS],
+    trace_only: bool,
+    rpl: [u8; RPLS],
+    ticked: bool,
+    sound_playing: bool,
+    quirks: Quirks,
+    cycles_per_frame: usize,
     hw: T,
 }
 
@@ -78,8 +131,20 @@ const REGS: usize = 16;
 const MEMS: usize = 4096;
 const STACKS: usize = 16;
 const DISPS: (usize, usize) = (64, 32);
+const SDISPS: (usize, usize) = (128, 64);
 const ENTRY: u16 = 512;
 const ROMBASE: usize = 512;
+const BREAKPOINTS: usize = 16;
+const RPLS: usize = 8;
+const CYCLES_PER_FRAME: usize = 10;
+
+const STATE_MAGIC: [u8; 4] = *b"C8ST";
+const STATE_VERSION: u8 = 2;
+const STATE_HEADER_LEN: usize = 5; // magic + version
+const STATE_BODY_LEN: usize = REGS + 2 + 1 + 1 + 2 + 1 + MEMS + STACKS * 2 + RPLS + 1;
+
+/// Length in bytes of a snapshot produced by [`Chip8::save_state`].
+pub const STATE_LEN: usize = STATE_HEADER_LEN + STATE_BODY_LEN;
 
 static CHARBUF: [u8; 80] = [
     0xf0, 0x90, 0x90, 0x90, 0xf0, // 0
@@ -100,9 +165,256 @@ static CHARBUF: [u8; 80] = [
     0xf0, 0x80, 0xf0, 0x80, 0x80, // f
 ];
 
+/// Offset of the SCHIP hi-res (10-byte-per-glyph) font table within `mem`.
+const HICHARBUF_BASE: usize = CHARBUF.len();
+
+static HICHARBUF: [u8; 160] = [
+    0x3c, 0x7e, 0xe7, 0xc3, 0xc3, 0xc3, 0xc3, 0xe7, 0x7e, 0x3c, // 0
+    0x18, 0x38, 0x58, 0x18, 0x18, 0x18, 0x18, 0x18, 0x18, 0x3c, // 1
+    0x3e, 0x7f, 0xc3, 0x06, 0x0c, 0x18, 0x30, 0x60, 0xff, 0xff, // 2
+    0x3c, 0x7e, 0xc3, 0x03, 0x0e, 0x0e, 0x03, 0xc3, 0x7e, 0x3c, // 3
+    0x06, 0x0e, 0x1e, 0x36, 0x66, 0xc6, 0xff, 0xff, 0x06, 0x06, // 4
+    0xff, 0xff, 0xc0, 0xc0, 0xfc, 0xfe, 0x03, 0xc3, 0x7e, 0x3c, // 5
+    0x3e, 0x7c, 0xc0, 0xc0, 0xfc, 0xfe, 0xc3, 0xc3, 0x7e, 0x3c, // 6
+    0xff, 0xff, 0x03, 0x06, 0x0c, 0x18, 0x30, 0x60, 0x60, 0x60, // 7
+    0x3c, 0x7e, 0xc3, 0xc3, 0x7e, 0x7e, 0xc3, 0xc3, 0x7e, 0x3c, // 8
+    0x3c, 0x7e, 0xc3, 0xc3, 0x7f, 0x3f, 0x03, 0x03, 0x3e, 0x7c, // 9
+    0x18, 0x3c, 0x66, 0xc3, 0xc3, 0xff, 0xff, 0xc3, 0xc3, 0xc3, // a
+    0xfc, 0xfe, 0xc3, 0xc3, 0xfc, 0xfe, 0xc3, 0xc3, 0xfe, 0xfc, // b
+    0x3c, 0x7e, 0xc3, 0xc0, 0xc0, 0xc0, 0xc0, 0xc3, 0x7e, 0x3c, // c
+    0xfc, 0xfe, 0xc3, 0xc3, 0xc3, 0xc3, 0xc3, 0xc3, 0xfe, 0xfc, // d
+    0xff, 0xff, 0xc0, 0xc0, 0xfc, 0xfc, 0xc0, 0xc0, 0xff, 0xff, // e
+    0xff, 0xff, 0xc0, 0xc0, 0xfc, 0xfc, 0xc0, 0xc0, 0xc0, 0xc0, // f
+];
+
+/// A decoded CHIP-8 instruction.
+///
+/// Returned by [`decode`], which is a pure function from opcode to instruction with
+/// no side effects, so it can be reused by disassemblers, coverage tools, or a
+/// debugger without executing anything. `eval` decodes every opcode through it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Instruction {
+    /// `00Cn` - SCHIP: scroll the screen down `n` pixel rows.
+    ScrollDown(usize),
+    /// `00E0` - Clear the screen.
+    Cls,
+    /// `00EE` - Return from a subroutine.
+    Ret,
+    /// `00FB` - SCHIP: scroll the screen right by 4 pixels.
+    ScrollRight,
+    /// `00FC` - SCHIP: scroll the screen left by 4 pixels.
+    ScrollLeft,
+    /// `00FD` - SCHIP: exit the interpreter.
+    Exit,
+    /// `00FE` - SCHIP: switch to low-res (64x32) mode.
+    Low,
+    /// `00FF` - SCHIP: switch to extended, high-res (128x64) mode.
+    High,
+    /// `0nnn` - Call an RCA 1802 routine (unimplemented on this interpreter).
+    Sys(u16),
+    /// `1nnn` - Jump to `nnn`.
+    Jp(u16),
+    /// `2nnn` - Call subroutine at `nnn`.
+    Call(u16),
+    /// `3xkk` - Skip next instruction if `Vx == kk`.
+    SeVxKk(usize, u8),
+    /// `4xkk` - Skip next instruction if `Vx != kk`.
+    SneVxKk(usize, u8),
+    /// `5xy0` - Skip next instruction if `Vx == Vy`.
+    SeVxVy(usize, usize),
+    /// `6xkk` - Set `Vx = kk`.
+    LdVxKk(usize, u8),
+    /// `7xkk` - Set `Vx = Vx + kk`.
+    AddVxKk(usize, u8),
+    /// `8xy0` - Set `Vx = Vy`.
+    LdVxVy(usize, usize),
+    /// `8xy1` - Set `Vx = Vx | Vy`.
+    OrVxVy(usize, usize),
+    /// `8xy2` - Set `Vx = Vx & Vy`.
+    AndVxVy(usize, usize),
+    /// `8xy3` - Set `Vx = Vx ^ Vy`.
+    XorVxVy(usize, usize),
+    /// `8xy4` - Set `Vx = Vx + Vy`, `VF = carry`.
+    AddVxVy(usize, usize),
+    /// `8xy5` - Set `Vx = Vx - Vy`, `VF = not borrow`.
+    SubVxVy(usize, usize),
+    /// `8xy6` - Set `Vx = Vx >> 1`, `VF = carried out bit`.
+    ShrVxVy(usize, usize),
+    /// `8xy7` - Set `Vx = Vy - Vx`, `VF = not borrow`.
+    SubnVxVy(usize, usize),
+    /// `8xyE` - Set `Vx = Vx << 1`, `VF = carried out bit`.
+    ShlVxVy(usize, usize),
+    /// `9xy0` - Skip next instruction if `Vx != Vy`.
+    SneVxVy(usize, usize),
+    /// `Annn` - Set `I = nnn`.
+    LdINnn(u16),
+    /// `Bnnn` - Jump to `nnn + V0`.
+    JpV0Nnn(u16),
+    /// `Cxkk` - Set `Vx = random byte & kk`.
+    RndVxKk(usize, u8),
+    /// `Dxyn` - Draw an `n`-byte sprite at `(Vx, Vy)`, `VF = collision`.
+    Drw(usize, usize, usize),
+    /// `Dxy0` - SCHIP: draw a 16x16 sprite at `(Vx, Vy)`, `VF = collision`.
+    DrwBig(usize, usize),
+    /// `Ex9E` - Skip next instruction if key `Vx` is pressed.
+    SkpVx(usize),
+    /// `ExA1` - Skip next instruction if key `Vx` is not pressed.
+    SknpVx(usize),
+    /// `Fx07` - Set `Vx = DT`.
+    LdVxDt(usize),
+    /// `Fx0A` - Wait for a key press, store it in `Vx`.
+    LdVxK(usize),
+    /// `Fx15` - Set `DT = Vx`.
+    LdDtVx(usize),
+    /// `Fx18` - Set `ST = Vx`.
+    LdStVx(usize),
+    /// `Fx1E` - Set `I = I + Vx`.
+    AddIVx(usize),
+    /// `Fx29` - Set `I` to the address of the font sprite for digit `Vx`.
+    LdFVx(usize),
+    /// `Fx30` - SCHIP: set `I` to the address of the hi-res font sprite for digit `Vx`.
+    LdHfVx(usize),
+    /// `Fx33` - Store the BCD representation of `Vx` at `I`, `I+1`, `I+2`.
+    LdBVx(usize),
+    /// `Fx55` - Store `V0..=Vx` at memory starting at `I`.
+    LdIVx(usize),
+    /// `Fx65` - Read `V0..=Vx` from memory starting at `I`.
+    LdVxI(usize),
+    /// `Fx75` - SCHIP: store `V0..=Vx` (`x <= 7`) into the RPL flags.
+    LdRVx(usize),
+    /// `Fx85` - SCHIP: read `V0..=Vx` (`x <= 7`) from the RPL flags.
+    LdVxR(usize),
+    /// An opcode that does not match any known instruction.
+    Invalid(u16),
+}
+
+/// Decode a raw 16-bit opcode into an [`Instruction`], without executing it.
+pub fn decode(inst: u16) -> Instruction {
+    let nnn = inst & 0xfff;
+    let n = (inst & 0xf) as usize;
+    let x = ((inst >> 8) & 0xf) as usize;
+    let y = ((inst >> 4) & 0xf) as usize;
+    let kk = (inst & 0xff) as u8;
+
+    match (
+        (inst >> 12) & 0xf,
+        (inst >> 8) & 0xf,
+        (inst >> 4) & 0xf,
+        (inst >> 0) & 0xf,
+    ) {
+        (0, 0, 0xc, _) => Instruction::ScrollDown(n),
+        (0, 0, 0xe, 0) => Instruction::Cls,
+        (0, 0, 0xe, 0xe) => Instruction::Ret,
+        (0, 0, 0xf, 0xb) => Instruction::ScrollRight,
+        (0, 0, 0xf, 0xc) => Instruction::ScrollLeft,
+        (0, 0, 0xf, 0xd) => Instruction::Exit,
+        (0, 0, 0xf, 0xe) => Instruction::Low,
+        (0, 0, 0xf, 0xf) => Instruction::High,
+        (0, _, _, _) => Instruction::Sys(nnn),
+        (1, _, _, _) => Instruction::Jp(nnn),
+        (2, _, _, _) => Instruction::Call(nnn),
+        (3, _, _, _) => Instruction::SeVxKk(x, kk),
+        (4, _, _, _) => Instruction::SneVxKk(x, kk),
+        (5, _, _, 0) => Instruction::SeVxVy(x, y),
+        (6, _, _, _) => Instruction::LdVxKk(x, kk),
+        (7, _, _, _) => Instruction::AddVxKk(x, kk),
+        (8, _, _, 0) => Instruction::LdVxVy(x, y),
+        (8, _, _, 1) => Instruction::OrVxVy(x, y),
+        (8, _, _, 2) => Instruction::AndVxVy(x, y),
+        (8, _, _, 3) => Instruction::XorVxVy(x, y),
+        (8, _, _, 4) => Instruction::AddVxVy(x, y),
+        (8, _, _, 5) => Instruction::SubVxVy(x, y),
+        (8, _, _, 6) => Instruction::ShrVxVy(x, y),
+        (8, _, _, 7) => Instruction::SubnVxVy(x, y),
+        (8, _, _, 0xe) => Instruction::ShlVxVy(x, y),
+        (9, _, _, 0) => Instruction::SneVxVy(x, y),
+        (0xa, _, _, _) => Instruction::LdINnn(nnn),
+        (0xb, _, _, _) => Instruction::JpV0Nnn(nnn),
+        (0xc, _, _, _) => Instruction::RndVxKk(x, kk),
+        (0xd, _, _, 0) => Instruction::DrwBig(x, y),
+        (0xd, _, _, _) => Instruction::Drw(x, y, n),
+        (0xe, _, 9, 0xe) => Instruction::SkpVx(x),
+        (0xe, _, 0xa, 0x1) => Instruction::SknpVx(x),
+        (0xf, _, 0, 7) => Instruction::LdVxDt(x),
+        (0xf, _, 0, 0xa) => Instruction::LdVxK(x),
+        (0xf, _, 1, 5) => Instruction::LdDtVx(x),
+        (0xf, _, 1, 8) => Instruction::LdStVx(x),
+        (0xf, _, 1, 0xe) => Instruction::AddIVx(x),
+        (0xf, _, 2, 9) => Instruction::LdFVx(x),
+        (0xf, _, 3, 0) => Instruction::LdHfVx(x),
+        (0xf, _, 3, 3) => Instruction::LdBVx(x),
+        (0xf, _, 5, 5) => Instruction::LdIVx(x),
+        (0xf, _, 6, 5) => Instruction::LdVxI(x),
+        (0xf, _, 7, 5) => Instruction::LdRVx(x),
+        (0xf, _, 8, 5) => Instruction::LdVxR(x),
+        _ => Instruction::Invalid(inst),
+    }
+}
+
+impl fmt::Display for Instruction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Instruction::ScrollDown(n) => write!(f, "SCD {:#03x}", n),
+            Instruction::Cls => write!(f, "CLS"),
+            Instruction::Ret => write!(f, "RET"),
+            Instruction::ScrollRight => write!(f, "SCR"),
+            Instruction::ScrollLeft => write!(f, "SCL"),
+            Instruction::Exit => write!(f, "EXIT"),
+            Instruction::Low => write!(f, "LOW"),
+            Instruction::High => write!(f, "HIGH"),
+            Instruction::Sys(nnn) => write!(f, "SYS {:#05x}", nnn),
+            Instruction::Jp(nnn) => write!(f, "JP {:#05x}", nnn),
+            Instruction::Call(nnn) => write!(f, "CALL {:#05x}", nnn),
+            Instruction::SeVxKk(x, kk) => write!(f, "SE V{:x}, {:#04x}", x, kk),
+            Instruction::SneVxKk(x, kk) => write!(f, "SNE V{:x}, {:#04x}", x, kk),
+            Instruction::SeVxVy(x, y) => write!(f, "SE V{:x}, V{:x}", x, y),
+            Instruction::LdVxKk(x, kk) => write!(f, "LD V{:x}, {:#04x}", x, kk),
+            Instruction::AddVxKk(x, kk) => write!(f, "ADD V{:x}, {:#04x}", x, kk),
+            Instruction::LdVxVy(x, y) => write!(f, "LD V{:x}, V{:x}", x, y),
+            Instruction::OrVxVy(x, y) => write!(f, "OR V{:x}, V{:x}", x, y),
+            Instruction::AndVxVy(x, y) => write!(f, "AND V{:x}, V{:x}", x, y),
+            Instruction::XorVxVy(x, y) => write!(f, "XOR V{:x}, V{:x}", x, y),
+            Instruction::AddVxVy(x, y) => write!(f, "ADD V{:x}, V{:x}", x, y),
+            Instruction::SubVxVy(x, y) => write!(f, "SUB V{:x}, V{:x}", x, y),
+            Instruction::ShrVxVy(x, y) => write!(f, "SHR V{:x}, V{:x}", x, y),
+            Instruction::SubnVxVy(x, y) => write!(f, "SUBN V{:x}, V{:x}", x, y),
+            Instruction::ShlVxVy(x, y) => write!(f, "SHL V{:x}, V{:x}", x, y),
+            Instruction::SneVxVy(x, y) => write!(f, "SNE V{:x}, V{:x}", x, y),
+            Instruction::LdINnn(nnn) => write!(f, "LD I, {:#05x}", nnn),
+            Instruction::JpV0Nnn(nnn) => write!(f, "JP V0, {:#05x}", nnn),
+            Instruction::RndVxKk(x, kk) => write!(f, "RND V{:x}, {:#04x}", x, kk),
+            Instruction::Drw(x, y, n) => write!(f, "DRW V{:x}, V{:x}, {:#03x}", x, y, n),
+            Instruction::DrwBig(x, y) => write!(f, "DRW V{:x}, V{:x}, {:#03x}", x, y, 0),
+            Instruction::SkpVx(x) => write!(f, "SKP V{:x}", x),
+            Instruction::SknpVx(x) => write!(f, "SKNP V{:x}", x),
+            Instruction::LdVxDt(x) => write!(f, "LD V{:x}, DT", x),
+            Instruction::LdVxK(x) => write!(f, "LD V{:x}, K", x),
+            Instruction::LdDtVx(x) => write!(f, "LD DT, V{:x}", x),
+            Instruction::LdStVx(x) => write!(f, "LD ST, V{:x}", x),
+            Instruction::AddIVx(x) => write!(f, "ADD I, V{:x}", x),
+            Instruction::LdFVx(x) => write!(f, "LD F, V{:x}", x),
+            Instruction::LdHfVx(x) => write!(f, "LD HF, V{:x}", x),
+            Instruction::LdBVx(x) => write!(f, "LD B, V{:x}", x),
+            Instruction::LdIVx(x) => write!(f, "LD [I], V{:x}", x),
+            Instruction::LdVxI(x) => write!(f, "LD V{:x}, [I]", x),
+            Instruction::LdRVx(x) => write!(f, "LD R, V{:x}", x),
+            Instruction::LdVxR(x) => write!(f, "LD V{:x}, R", x),
+            Instruction::Invalid(inst) => write!(f, "??? {:#06x}", inst),
+        }
+    }
+}
+
 impl<T: Hardware> Chip8<T> {
     /// Create an interpreter instance.
     pub fn new(hw: T) -> Self {
+        Self::with_quirks(hw, Quirks::default())
+    }
+
+    /// Create an interpreter instance with specific compatibility quirks.
+    ///
+    /// Use this instead of `new` when the ROM being run needs non-default
+    /// behavior for one of the well-known ambiguous CHIP-8 opcodes.
+    pub fn with_quirks(hw: T, quirks: Quirks) -> Self {
         Self {
             v: [0; REGS],
             i: 0,
@@ -114,31 +426,325 @@ impl<T: Hardware> Chip8<T> {
             stack: [0; STACKS],
             time: None,
             running: false,
+            breakpoints: [None; BREAKPOINTS],
+            trace_only: false,
+            rpl: [0; RPLS],
+            ticked: false,
+            sound_playing: false,
+            quirks,
+            cycles_per_frame: CYCLES_PER_FRAME,
             hw,
         }
     }
 
+    /// Change the compatibility quirks used for subsequent instructions.
+    pub fn set_quirks(&mut self, quirks: Quirks) {
+        self.quirks = quirks;
+    }
+
+    /// Set how many instructions `exec_frame` (and thus `run`) executes per
+    /// 60 Hz frame. Defaults to 10.
+    pub fn set_cycles_per_frame(&mut self, cycles_per_frame: usize) {
+        self.cycles_per_frame = cycles_per_frame;
+    }
+
     /// Run the interpreter.
     ///
     /// The argument takes the raw ROM binary.
     pub fn run(mut self, rom: &[u8]) {
-        self.setup();
-        self.load(rom);
+        self.init(rom);
 
         while self.running {
-            self.sched();
+            self.exec_frame();
+        }
+    }
+
+    /// Run one frame's worth of cycles: execute `cycles_per_frame` instructions,
+    /// gating the 60 Hz timer decrement on the host clock exactly once.
+    ///
+    /// This decouples emulation speed from the host scheduler: the host no
+    /// longer has to sleep between individual instructions, only between
+    /// frames. Embedders driving their own render loop can call this directly
+    /// instead of `run`.
+    pub fn exec_frame(&mut self) {
+        self.sched();
+
+        for _ in 0..self.cycles_per_frame {
+            if !self.running {
+                break;
+            }
             self.eval();
             self.next();
         }
     }
 
+    /// Set up the machine and load a ROM, without starting execution.
+    ///
+    /// This is the entry point for embedders that want to drive the interpreter
+    /// one instruction at a time via `step` instead of handing control over to
+    /// `run`, e.g. to build a debugger.
+    pub fn init(&mut self, rom: &[u8]) {
+        self.setup();
+        self.load(rom);
+    }
+
+    /// Read a general-purpose register `Vx`. Returns `None` if `x` is out of range.
+    pub fn reg(&self, x: usize) -> Option<u8> {
+        self.v.get(x).copied()
+    }
+
+    /// Patch a general-purpose register `Vx`. Returns `Err(())` if `x` is out of range.
+    pub fn set_reg(&mut self, x: usize, val: u8) -> Result<(), ()> {
+        *self.v.get_mut(x).ok_or(())? = val;
+        Ok(())
+    }
+
+    /// Read the `I` register.
+    pub fn i(&self) -> u16 {
+        self.i
+    }
+
+    /// Read the program counter.
+    pub fn pc(&self) -> u16 {
+        self.pc
+    }
+
+    /// Read the stack pointer.
+    pub fn sp(&self) -> u8 {
+        self.sp
+    }
+
+    /// Read a byte of memory. Returns `None` if `addr` is out of range.
+    pub fn peek_mem(&self, addr: u16) -> Option<u8> {
+        self.mem.get(addr as usize).copied()
+    }
+
+    /// Patch a byte of memory. Returns `Err(())` if `addr` is out of range.
+    pub fn poke_mem(&mut self, addr: u16, val: u8) -> Result<(), ()> {
+        *self.mem.get_mut(addr as usize).ok_or(())? = val;
+        Ok(())
+    }
+
+    /// Read the call stack, oldest frame first.
+    pub fn stack(&self) -> &[u16] {
+        &self.stack[..self.sp as usize]
+    }
+
+    /// Serialize the complete machine state into a fixed-size buffer: registers,
+    /// `I`, the timers, `pc`, `sp`, memory, the call stack and the SCHIP RPL flags.
+    ///
+    /// The screen is not included, since VRAM lives in the `Hardware` impl; a
+    /// front-end that wants save-state screenshots should snapshot its own pixel
+    /// buffer alongside this call, or simply re-derive the screen by resuming
+    /// execution from the restored state.
+    pub fn save_state(&self) -> [u8; STATE_LEN] {
+        let mut buf = [0u8; STATE_LEN];
+
+        buf[0..4].copy_from_slice(&STATE_MAGIC);
+        buf[4] = STATE_VERSION;
+
+        let mut pos = STATE_HEADER_LEN;
+
+        buf[pos..pos + REGS].copy_from_slice(&self.v);
+        pos += REGS;
+
+        buf[pos..pos + 2].copy_from_slice(&self.i.to_le_bytes());
+        pos += 2;
+
+        buf[pos] = self.dt;
+        pos += 1;
+
+        buf[pos] = self.st;
+        pos += 1;
+
+        buf[pos..pos + 2].copy_from_slice(&self.pc.to_le_bytes());
+        pos += 2;
+
+        buf[pos] = self.sp;
+        pos += 1;
+
+        buf[pos..pos + MEMS].copy_from_slice(&self.mem);
+        pos += MEMS;
+
+        for word in self.stack.iter() {
+            buf[pos..pos + 2].copy_from_slice(&word.to_le_bytes());
+            pos += 2;
+        }
+
+        buf[pos..pos + RPLS].copy_from_slice(&self.rpl);
+        pos += RPLS;
+
+        buf[pos] = self.running as u8;
+
+        buf
+    }
+
+    /// Restore a machine state previously produced by `save_state`.
+    ///
+    /// Returns `Err(())` if `data` has the wrong length or does not start with
+    /// the expected magic/version header, leaving `self` untouched.
+    pub fn load_state(&mut self, data: &[u8]) -> Result<(), ()> {
+        if data.len() != STATE_LEN || data[0..4] != STATE_MAGIC[..] || data[4] != STATE_VERSION {
+            return Err(());
+        }
+
+        let mut pos = STATE_HEADER_LEN;
+
+        self.v.copy_from_slice(&data[pos..pos + REGS]);
+        pos += REGS;
+
+        self.i = u16::from_le_bytes([data[pos], data[pos + 1]]);
+        pos += 2;
+
+        self.dt = data[pos];
+        pos += 1;
+
+        self.st = data[pos];
+        pos += 1;
+
+        self.pc = u16::from_le_bytes([data[pos], data[pos + 1]]);
+        pos += 2;
+
+        self.sp = data[pos];
+        pos += 1;
+
+        self.mem.copy_from_slice(&data[pos..pos + MEMS]);
+        pos += MEMS;
+
+        for word in self.stack.iter_mut() {
+            *word = u16::from_le_bytes([data[pos], data[pos + 1]]);
+            pos += 2;
+        }
+
+        self.rpl.copy_from_slice(&data[pos..pos + RPLS]);
+        pos += RPLS;
+
+        self.running = data[pos] != 0;
+
+        // `sound_playing` isn't part of the snapshot, so re-derive the
+        // on/off edge from the restored `st` instead of leaving the buzzer
+        // stuck on if it was playing when the snapshot was taken.
+        self.update_sound();
+
+        Ok(())
+    }
+
+    /// Stop execution at `addr` the next time it is reached.
+    ///
+    /// If `trace_only` is set, the breakpoint logs a trace line instead of
+    /// halting `run_until_breakpoint`.
+    ///
+    /// At most 16 breakpoints can be set at once; while all of them are in
+    /// use, further calls are ignored.
+    pub fn set_breakpoint(&mut self, addr: u16) {
+        if self.breakpoints.contains(&Some(addr)) {
+            return;
+        }
+        if let Some(slot) = self.breakpoints.iter_mut().find(|b| b.is_none()) {
+            *slot = Some(addr);
+        }
+    }
+
+    /// Remove a previously set breakpoint.
+    pub fn clear_breakpoint(&mut self, addr: u16) {
+        for slot in self.breakpoints.iter_mut() {
+            if *slot == Some(addr) {
+                *slot = None;
+            }
+        }
+    }
+
+    /// When set, a hit breakpoint is traced but does not halt `run_until_breakpoint`.
+    pub fn set_trace_only(&mut self, trace_only: bool) {
+        self.trace_only = trace_only;
+    }
+
+    fn is_breakpoint(&self, addr: u16) -> bool {
+        self.breakpoints.contains(&Some(addr))
+    }
+
+    /// Run exactly one fetch/eval/next cycle and return the address that was executed.
+    ///
+    /// This is the building block for single-stepping debuggers: unlike `run`, it
+    /// does not consume `self`, so the caller keeps control between instructions.
+    pub fn step(&mut self) -> u16 {
+        self.sched();
+        let pc = self.pc;
+        self.eval();
+        self.next();
+        pc
+    }
+
+    /// Run until a breakpoint is reached or the machine shuts down.
+    ///
+    /// Returns `true` if stopped because of a breakpoint, in which case the
+    /// breakpointed instruction has not executed yet and `step` resumes from it.
+    /// Returns `false` if the interpreter shut down normally.
+    pub fn run_until_breakpoint(&mut self) -> bool {
+        while self.running {
+            if self.is_breakpoint(self.pc) {
+                trace!("[{:04x}] breakpoint", self.pc);
+                if !self.trace_only {
+                    return true;
+                }
+            }
+            self.step();
+        }
+        false
+    }
+
     fn setup(&mut self) {
         self.pc = ENTRY;
         self.hw.vram_setsize(DISPS);
         self.mem[..CHARBUF.len()].copy_from_slice(&CHARBUF);
+        self.mem[HICHARBUF_BASE..HICHARBUF_BASE + HICHARBUF.len()].copy_from_slice(&HICHARBUF);
         self.running = true;
     }
 
+    fn scroll_down(&mut self, n: usize) {
+        let (w, h) = self.hw.vram_size();
+
+        for y in (n..h).rev() {
+            for x in 0..w {
+                let px = self.hw.vram_get(x, y - n);
+                self.hw.vram_set(x, y, px);
+            }
+        }
+        for y in 0..n.min(h) {
+            for x in 0..w {
+                self.hw.vram_set(x, y, false);
+            }
+        }
+    }
+
+    fn scroll_right(&mut self) {
+        let (w, h) = self.hw.vram_size();
+
+        for y in 0..h {
+            for x in (4..w).rev() {
+                let px = self.hw.vram_get(x - 4, y);
+                self.hw.vram_set(x, y, px);
+            }
+            for x in 0..w.min(4) {
+                self.hw.vram_set(x, y, false);
+            }
+        }
+    }
+
+    fn scroll_left(&mut self) {
+        let (w, h) = self.hw.vram_size();
+
+        for y in 0..h {
+            for x in 0..w.saturating_sub(4) {
+                let px = self.hw.vram_get(x + 4, y);
+                self.hw.vram_set(x, y, px);
+            }
+            for x in w.saturating_sub(4)..w {
+                self.hw.vram_set(x, y, false);
+            }
+        }
+    }
+
     fn shutdown(&mut self) {
         self.running = false;
     }
@@ -188,9 +794,27 @@ impl<T: Hardware> Chip8<T> {
         }
         if self.st > 0 {
             self.st -= 1;
-            if self.st == 0 {
-                self.hw.beep();
-            }
+            self.update_sound();
+        }
+        self.ticked = true;
+    }
+
+    /// Call `sound_on`/`sound_off` exactly once on each edge of `st`.
+    fn update_sound(&mut self) {
+        if self.st > 0 && !self.sound_playing {
+            self.sound_playing = true;
+            self.hw.sound_on();
+        } else if self.st == 0 && self.sound_playing {
+            self.sound_playing = false;
+            self.hw.sound_off();
+        }
+    }
+
+    /// Block until the next 60 Hz tick, for the `display_wait` quirk.
+    fn wait_vblank(&mut self) {
+        self.ticked = false;
+        while self.running && !self.ticked {
+            self.sched();
         }
     }
 
@@ -213,133 +837,130 @@ impl<T: Hardware> Chip8<T> {
         let l = self.mem[(self.pc + 1) as usize] as u16;
         let inst = h << 8 | l;
 
-        let nnn = inst & 0xfff;
-        let n = (inst & 0xf) as usize;
-        let x = ((inst >> 8) & 0xf) as usize;
-        let y = ((inst >> 4) & 0xf) as usize;
-        let kk = (inst & 0xff) as u8;
-
-        match (
-            (inst >> 12) & 0xf,
-            (inst >> 8) & 0xf,
-            (inst >> 4) & 0xf,
-            (inst >> 0) & 0xf,
-        ) {
-            (0, 0, 0xe, 0) => {
-                trace!("[{:04x}] CLS", self.pc);
+        let inst = decode(inst);
+        trace!("[{:04x}] {}", self.pc, inst);
+
+        match inst {
+            Instruction::ScrollDown(n) => self.scroll_down(n),
+            Instruction::Cls => {
                 let (w, h) = self.hw.vram_size();
                 for (x, y) in (0..w).map(|w| (0..h).map(move |h| (w, h))).flatten() {
                     self.hw.vram_set(x, y, false);
                 }
             }
-            (0, 0, 0xe, 0xe) => {
-                trace!("[{:04x}] RET", self.pc);
+            Instruction::Ret => {
                 let addr = self.pop();
                 self.jump(addr);
             }
-            (0, _, _, _) => {
-                trace!("[{:04x}] SYS nnn", self.pc);
-                unimplemented!()
-            }
-            (1, _, _, _) => {
-                trace!("[{:04x}] JP nnn", self.pc);
+            Instruction::ScrollRight => self.scroll_right(),
+            Instruction::ScrollLeft => self.scroll_left(),
+            Instruction::Exit => self.shutdown(),
+            Instruction::Low => self.hw.vram_setsize(DISPS),
+            Instruction::High => self.hw.vram_setsize(SDISPS),
+            Instruction::Sys(_) => unimplemented!(),
+            Instruction::Jp(nnn) => {
                 self.jump(nnn.wrapping_sub(2));
             }
-            (2, _, _, _) => {
-                trace!("[{:04x}] CALL nnn", self.pc);
+            Instruction::Call(nnn) => {
                 self.push(self.pc);
                 self.jump(nnn.wrapping_sub(2));
             }
-            (3, _, _, _) => {
-                trace!("[{:04x}] SE Vx kk", self.pc);
+            Instruction::SeVxKk(x, kk) => {
                 if self.v[x] == kk {
                     self.next();
                 }
             }
-            (4, _, _, _) => {
-                trace!("[{:04x}] SNE Vx, kk", self.pc);
+            Instruction::SneVxKk(x, kk) => {
                 if self.v[x] != kk {
                     self.next();
                 }
             }
-            (5, _, _, 0) => {
-                trace!("[{:04x}] SE Vx, Vy", self.pc);
+            Instruction::SeVxVy(x, y) => {
                 if self.v[x] == self.v[y] {
                     self.next();
                 }
             }
-            (6, _, _, _) => {
-                trace!("[{:04x}] LD Vx, kk", self.pc);
+            Instruction::LdVxKk(x, kk) => {
                 self.v[x] = kk;
             }
-            (7, _, _, _) => {
-                trace!("[{:04x}] ADD Vx, kk", self.pc);
+            Instruction::AddVxKk(x, kk) => {
                 self.v[x] = self.v[x].wrapping_add(kk);
             }
-            (8, _, _, 0) => {
-                trace!("[{:04x}] LD Vx, Vy", self.pc);
+            Instruction::LdVxVy(x, y) => {
                 self.v[x] = self.v[y];
             }
-            (8, _, _, 1) => {
-                trace!("[{:04x}] OR Vx, Vy", self.pc);
+            Instruction::OrVxVy(x, y) => {
                 self.v[x] |= self.v[y];
+                if self.quirks.vf_reset {
+                    self.v[0xf] = 0;
+                }
             }
-            (8, _, _, 2) => {
-                trace!("[{:04x}] AND Vx, Vy", self.pc);
+            Instruction::AndVxVy(x, y) => {
                 self.v[x] &= self.v[y];
+                if self.quirks.vf_reset {
+                    self.v[0xf] = 0;
+                }
             }
-            (8, _, _, 3) => {
-                trace!("[{:04x}] XOR Vx, Vy", self.pc);
+            Instruction::XorVxVy(x, y) => {
                 self.v[x] ^= self.v[y];
+                if self.quirks.vf_reset {
+                    self.v[0xf] = 0;
+                }
             }
-            (8, _, _, 4) => {
-                trace!("[{:04x}] ADD Vx, Vy", self.pc);
+            Instruction::AddVxVy(x, y) => {
                 let (v, c) = self.v[x].overflowing_add(self.v[y]);
                 self.v[x] = v;
                 self.v[0xf] = c as u8;
             }
-            (8, _, _, 5) => {
-                trace!("[{:04x}] SUB Vx, Vy", self.pc);
+            Instruction::SubVxVy(x, y) => {
                 let (v, b) = self.v[x].overflowing_sub(self.v[y]);
                 self.v[x] = v;
                 self.v[0xf] = !b as u8;
             }
-            (8, _, _, 6) => {
-                trace!("[{:04x}] SHR Vx, Vy", self.pc);
+            Instruction::ShrVxVy(x, y) => {
+                if !self.quirks.shift_in_place {
+                    self.v[x] = self.v[y];
+                }
                 self.v[0xf] = self.v[x] & 1;
                 self.v[x] = self.v[x].wrapping_shr(1);
             }
-            (8, _, _, 7) => {
-                trace!("[{:04x}] SUBN Vx, Vy", self.pc);
+            Instruction::SubnVxVy(x, y) => {
                 let (v, b) = self.v[y].overflowing_sub(self.v[x]);
                 self.v[x] = v;
                 self.v[0xf] = !b as u8;
             }
-            (8, _, _, 0xe) => {
-                trace!("[{:04x}] SHL Vx, Vy", self.pc);
+            Instruction::ShlVxVy(x, y) => {
+                if !self.quirks.shift_in_place {
+                    self.v[x] = self.v[y];
+                }
                 self.v[0xf] = (self.v[x] & 0x80) >> 7;
                 self.v[x] = self.v[x].wrapping_shl(1);
             }
-            (9, _, _, 0) => {
-                trace!("[{:04x}] SNE Vx, Vy", self.pc);
+            Instruction::SneVxVy(x, y) => {
                 if self.v[x] != self.v[y] {
                     self.next();
                 }
             }
-            (0xa, _, _, _) => {
-                trace!("[{:04x}] LD I, nnn", self.pc);
+            Instruction::LdINnn(nnn) => {
                 self.i = nnn;
             }
-            (0xb, _, _, _) => {
-                trace!("[{:04x}] JP V0, nnn", self.pc);
-                self.jump(nnn.wrapping_add(self.v[0].into()).wrapping_sub(2));
+            Instruction::JpV0Nnn(nnn) => {
+                let base = if self.quirks.jump_vx {
+                    let x = ((nnn >> 8) & 0xf) as usize;
+                    self.v[x]
+                } else {
+                    self.v[0]
+                };
+                self.jump(nnn.wrapping_add(base.into()).wrapping_sub(2));
             }
-            (0xc, _, _, _) => {
-                trace!("[{:04x}] RND Vx, kk", self.pc);
+            Instruction::RndVxKk(x, kk) => {
                 self.v[x] = self.hw.rand() & kk;
             }
-            (0xd, _, _, _) => {
-                trace!("[{:04x}] DRW Vx, Vy, n", self.pc);
+            Instruction::Drw(x, y, n) => {
+                if self.quirks.display_wait {
+                    self.wait_vblank();
+                }
+
                 let basex = self.v[x] as usize;
                 let basey = self.v[y] as usize;
                 let (w, h) = self.hw.vram_size();
@@ -363,64 +984,611 @@ impl<T: Hardware> Chip8<T> {
                     }
                 }
             }
-            (0xe, _, 9, 0xe) => {
-                trace!("[{:04x}] SKP Vx", self.pc);
+            Instruction::DrwBig(x, y) => {
+                if self.quirks.display_wait {
+                    self.wait_vblank();
+                }
+
+                let basex = self.v[x] as usize;
+                let basey = self.v[y] as usize;
+                let (w, h) = self.hw.vram_size();
+
+                self.v[0xf] = 0;
+
+                for row in 0..16 {
+                    let b0 = self.mem[self.i as usize + row * 2] as u16;
+                    let b1 = self.mem[self.i as usize + row * 2 + 1] as u16;
+                    let bits = b0 << 8 | b1;
+
+                    let vramy = (row + basey) % h;
+
+                    for col in 0..16 {
+                        let vramx = (col + basex) % w;
+
+                        let src = (bits & 1 << (15 - col)) > 0;
+                        let dst = self.hw.vram_get(vramx, vramy);
+
+                        self.v[0xf] |= (src && dst) as u8;
+
+                        self.hw.vram_set(vramx, vramy, src ^ dst);
+                    }
+                }
+            }
+            Instruction::SkpVx(x) => {
                 if self.hw.key(self.v[x]) {
                     self.next();
                 }
             }
-            (0xe, _, 0xa, 0x1) => {
-                trace!("[{:04x}] SKNP Vx", self.pc);
+            Instruction::SknpVx(x) => {
                 if !self.hw.key(self.v[x]) {
                     self.next();
                 }
             }
-            (0xf, _, 0, 7) => {
-                trace!("[{:04x}] LD Vx, DT", self.pc);
+            Instruction::LdVxDt(x) => {
                 self.v[x] = self.dt;
             }
-            (0xf, _, 0, 0xa) => {
-                trace!("[{:04x}] LD Vx, K", self.pc);
+            Instruction::LdVxK(x) => {
                 self.v[x] = self.waitkey();
             }
-            (0xf, _, 1, 5) => {
-                trace!("[{:04x}] LD DT, Vx", self.pc);
+            Instruction::LdDtVx(x) => {
                 self.dt = self.v[x];
             }
-            (0xf, _, 1, 8) => {
-                trace!("[{:04x}] LD ST, Vx", self.pc);
+            Instruction::LdStVx(x) => {
                 self.st = self.v[x];
+                self.update_sound();
             }
-            (0xf, _, 1, 0xe) => {
-                trace!("[{:04x}] ADD I, Vx", self.pc);
+            Instruction::AddIVx(x) => {
                 self.i = self.i.wrapping_add(self.v[x].into());
             }
-            (0xf, _, 2, 9) => {
-                trace!("[{:04x}] LD F, Vx", self.pc);
+            Instruction::LdFVx(x) => {
                 self.i = (self.v[x] * 5).into();
             }
-            (0xf, _, 3, 3) => {
-                trace!("[{:04x}] LD B, Vx", self.pc);
+            Instruction::LdHfVx(x) => {
+                self.i = (HICHARBUF_BASE + self.v[x] as usize * 10) as u16;
+            }
+            Instruction::LdBVx(x) => {
                 let bcd = self.v[x];
                 self.mem[self.i as usize] = (bcd / 100) % 10;
                 self.mem[self.i as usize + 1] = (bcd / 10) % 10;
                 self.mem[self.i as usize + 2] = bcd % 10;
             }
-            (0xf, _, 5, 5) => {
-                trace!("[{:04x}] LD [I], Vx", self.pc);
-                let x = x as usize;
+            Instruction::LdIVx(x) => {
                 for i in 0..(x + 1) {
                     self.mem[self.i as usize + i] = self.v[i];
                 }
+                if self.quirks.load_store_increment {
+                    self.i = self.i.wrapping_add((x + 1) as u16);
+                }
             }
-            (0xf, _, 6, 5) => {
-                trace!("[{:04x}] LD Vx, [I]", self.pc);
-                let x = x as usize;
+            Instruction::LdVxI(x) => {
                 for i in 0..(x + 1) {
                     self.v[i] = self.mem[self.i as usize + i];
                 }
+                if self.quirks.load_store_increment {
+                    self.i = self.i.wrapping_add((x + 1) as u16);
+                }
+            }
+            Instruction::LdRVx(x) => {
+                for i in 0..=x.min(RPLS - 1) {
+                    self.rpl[i] = self.v[i];
+                }
+            }
+            Instruction::LdVxR(x) => {
+                for i in 0..=x.min(RPLS - 1) {
+                    self.v[i] = self.rpl[i];
+                }
+            }
+            Instruction::Invalid(inst) => panic!("[{:04x}] Invalid op: {:04x}", self.pc, inst),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::string::ToString;
+    use std::vec;
+
+    /// Minimal in-memory `Hardware` impl for exercising `Chip8` without a
+    /// real display or audio device.
+    struct MockHw {
+        vram: std::vec::Vec<bool>,
+        vramsz: (usize, usize),
+        clock: u64,
+        clock_calls: u32,
+        // Once set, every `clock()` call jumps the clock far past the 60 Hz
+        // threshold, so `sched` ticks deterministically without real sleeps.
+        ticking: bool,
+        sound_on_count: u32,
+        sound_off_count: u32,
+    }
+
+    impl MockHw {
+        fn new() -> Self {
+            Self {
+                vram: vec![false; DISPS.0 * DISPS.1],
+                vramsz: DISPS,
+                clock: 0,
+                clock_calls: 0,
+                ticking: false,
+                sound_on_count: 0,
+                sound_off_count: 0,
             }
-            _ => panic!("[{:04x}] Invalid op: {:04x}", self.pc, inst), // Bad ops
         }
     }
+
+    impl Hardware for MockHw {
+        fn rand(&mut self) -> u8 {
+            0
+        }
+
+        fn key(&mut self, _key: u8) -> bool {
+            false
+        }
+
+        fn vram_set(&mut self, x: usize, y: usize, d: bool) {
+            self.vram[y * self.vramsz.0 + x] = d;
+        }
+
+        fn vram_get(&mut self, x: usize, y: usize) -> bool {
+            self.vram[y * self.vramsz.0 + x]
+        }
+
+        fn vram_setsize(&mut self, size: (usize, usize)) {
+            self.vramsz = size;
+            self.vram = vec![false; size.0 * size.1];
+        }
+
+        fn vram_size(&mut self) -> (usize, usize) {
+            self.vramsz
+        }
+
+        fn clock(&mut self) -> u64 {
+            self.clock_calls += 1;
+            if self.ticking {
+                self.clock += 1_000_000_000;
+            }
+            self.clock
+        }
+
+        fn sound_on(&mut self) {
+            self.sound_on_count += 1;
+        }
+
+        fn sound_off(&mut self) {
+            self.sound_off_count += 1;
+        }
+    }
+
+    #[test]
+    fn step_and_breakpoint_drive_a_debugger_loop() {
+        let rom = [
+            0x60, 0x01, // LD V0, 0x01
+            0x61, 0x02, // LD V1, 0x02
+            0x12, 0x04, // JP 0x204 (loop forever)
+        ];
+        let mut chip8 = Chip8::new(MockHw::new());
+        chip8.init(&rom);
+
+        chip8.set_breakpoint(ENTRY + 2);
+        assert!(chip8.run_until_breakpoint());
+        assert_eq!(chip8.pc(), ENTRY + 2);
+        assert_eq!(chip8.reg(0).unwrap(), 1);
+        assert_eq!(chip8.reg(1).unwrap(), 0);
+
+        chip8.clear_breakpoint(ENTRY + 2);
+        let pc = chip8.step();
+        assert_eq!(pc, ENTRY + 2);
+        assert_eq!(chip8.reg(1).unwrap(), 2);
+    }
+
+    #[test]
+    fn out_of_range_reg_and_mem_accessors_report_an_error_instead_of_panicking() {
+        let mut chip8 = Chip8::new(MockHw::new());
+        chip8.init(&[]);
+
+        assert_eq!(chip8.reg(16), None);
+        assert_eq!(chip8.set_reg(16, 0), Err(()));
+        assert_eq!(chip8.peek_mem(0x2000), None);
+        assert_eq!(chip8.poke_mem(0x2000, 0), Err(()));
+    }
+
+    #[test]
+    fn decode_round_trips_every_instruction_class() {
+        let cases = [
+            (0x00e0, "CLS"),
+            (0x00ee, "RET"),
+            (0x0123, "SYS 0x123"),
+            (0x1123, "JP 0x123"),
+            (0x2123, "CALL 0x123"),
+            (0x31ab, "SE V1, 0xab"),
+            (0x41ab, "SNE V1, 0xab"),
+            (0x5120, "SE V1, V2"),
+            (0x61ab, "LD V1, 0xab"),
+            (0x71ab, "ADD V1, 0xab"),
+            (0x8120, "LD V1, V2"),
+            (0x8121, "OR V1, V2"),
+            (0x8122, "AND V1, V2"),
+            (0x8123, "XOR V1, V2"),
+            (0x8124, "ADD V1, V2"),
+            (0x8125, "SUB V1, V2"),
+            (0x8126, "SHR V1, V2"),
+            (0x8127, "SUBN V1, V2"),
+            (0x812e, "SHL V1, V2"),
+            (0x9120, "SNE V1, V2"),
+            (0xa123, "LD I, 0x123"),
+            (0xb123, "JP V0, 0x123"),
+            (0xc1ab, "RND V1, 0xab"),
+            (0xd123, "DRW V1, V2, 0x3"),
+            (0x00c3, "SCD 0x3"),
+            (0x00fb, "SCR"),
+            (0x00fc, "SCL"),
+            (0x00fd, "EXIT"),
+            (0x00fe, "LOW"),
+            (0x00ff, "HIGH"),
+            (0xd120, "DRW V1, V2, 0x0"),
+            (0xf130, "LD HF, V1"),
+            (0xf175, "LD R, V1"),
+            (0xf185, "LD V1, R"),
+            (0xe19e, "SKP V1"),
+            (0xe1a1, "SKNP V1"),
+            (0xf107, "LD V1, DT"),
+            (0xf10a, "LD V1, K"),
+            (0xf115, "LD DT, V1"),
+            (0xf118, "LD ST, V1"),
+            (0xf11e, "ADD I, V1"),
+            (0xf129, "LD F, V1"),
+            (0xf133, "LD B, V1"),
+            (0xf155, "LD [I], V1"),
+            (0xf165, "LD V1, [I]"),
+            (0xffff, "??? 0xffff"),
+        ];
+
+        for (opcode, expected) in cases {
+            let decoded = decode(opcode);
+            assert_eq!(decoded.to_string(), expected, "opcode {:04x}", opcode);
+        }
+    }
+
+    #[test]
+    fn save_state_round_trips_machine_state() {
+        let rom = [0x60, 0x2a, 0x61, 0x10]; // LD V0, 0x2a ; LD V1, 0x10
+        let mut chip8 = Chip8::new(MockHw::new());
+        chip8.init(&rom);
+        chip8.step();
+        chip8.poke_mem(0x300, 0x42).unwrap();
+
+        let snapshot = chip8.save_state();
+
+        let mut restored = Chip8::new(MockHw::new());
+        restored.load_state(&snapshot).unwrap();
+
+        assert_eq!(restored.reg(0).unwrap(), chip8.reg(0).unwrap());
+        assert_eq!(restored.pc(), chip8.pc());
+        assert_eq!(restored.peek_mem(0x300).unwrap(), 0x42);
+    }
+
+    #[test]
+    fn load_state_rejects_wrong_length_or_magic() {
+        let mut chip8 = Chip8::new(MockHw::new());
+        assert_eq!(chip8.load_state(&[0u8; 4]), Err(()));
+
+        let mut bogus = chip8.save_state();
+        bogus[0] = 0;
+        assert_eq!(chip8.load_state(&bogus), Err(()));
+    }
+
+    #[test]
+    fn load_state_silences_a_buzzer_left_playing_before_the_snapshot() {
+        let rom = [0x60, 0x05, 0xf0, 0x18]; // LD V0, 5 ; LD ST, V0
+        let mut chip8 = Chip8::new(MockHw::new());
+        chip8.init(&rom);
+        chip8.step();
+        chip8.step();
+        assert_eq!(chip8.hw.sound_on_count, 1);
+
+        // Simulate a snapshot taken once the sound timer had already run out,
+        // while the buzzer from before the snapshot is still playing.
+        let mut snapshot = chip8.save_state();
+        let st_pos = STATE_HEADER_LEN + REGS + 2 + 1; // v + i + dt
+        snapshot[st_pos] = 0;
+
+        chip8.load_state(&snapshot).unwrap();
+        assert_eq!(chip8.hw.sound_off_count, 1);
+    }
+
+    #[test]
+    fn schip_scroll_moves_pixels_and_rpl_flags_round_trip() {
+        let rom = [
+            0x00, 0xff, // HIGH
+            0x00, 0xc4, // SCD 4
+            0x00, 0xfb, // SCR
+            0x00, 0xfc, // SCL
+            0x60, 0x09, // LD V0, 0x09
+            0xf0, 0x75, // LD R, V0
+            0x60, 0x00, // LD V0, 0x00
+            0xf0, 0x85, // LD V0, R
+        ];
+        let mut chip8 = Chip8::new(MockHw::new());
+        chip8.init(&rom);
+
+        chip8.step(); // HIGH
+        assert_eq!(chip8.hw.vram_size(), SDISPS);
+        chip8.hw.vram_set(0, 0, true);
+
+        chip8.step(); // SCD 4
+        assert!(chip8.hw.vram_get(0, 4));
+        assert!(!chip8.hw.vram_get(0, 0));
+
+        chip8.step(); // SCR
+        assert!(chip8.hw.vram_get(4, 4));
+        assert!(!chip8.hw.vram_get(0, 4));
+
+        chip8.step(); // SCL
+        assert!(chip8.hw.vram_get(0, 4));
+        assert!(!chip8.hw.vram_get(4, 4));
+
+        chip8.step(); // LD V0, 0x09
+        chip8.step(); // LD R, V0
+        chip8.step(); // LD V0, 0x00
+        assert_eq!(chip8.reg(0).unwrap(), 0);
+        chip8.step(); // LD V0, R
+        assert_eq!(chip8.reg(0).unwrap(), 9);
+    }
+
+    #[test]
+    fn drw_big_draws_16x16_sprite_with_xor_collision() {
+        let rom = [
+            0x00, 0xff, // HIGH
+            0xa3, 0x00, // LD I, 0x300
+            0x60, 0x00, // LD V0, 0
+            0x61, 0x00, // LD V1, 0
+            0xd0, 0x10, // DRW V0, V1, 0 (16x16 sprite)
+        ];
+        let mut chip8 = Chip8::new(MockHw::new());
+        chip8.init(&rom);
+        chip8.poke_mem(0x300, 0x80).unwrap(); // row 0: leftmost pixel set
+        chip8.poke_mem(0x301, 0x00).unwrap();
+
+        chip8.step(); // HIGH
+        chip8.step(); // LD I, 0x300
+        chip8.step(); // LD V0, 0
+        chip8.step(); // LD V1, 0
+        chip8.step(); // DRW V0, V1, 0
+
+        assert!(chip8.hw.vram_get(0, 0));
+        assert_eq!(chip8.reg(0xf).unwrap(), 0);
+
+        // Drawing the same sprite again XORs it back off and reports the collision.
+        chip8.jump(ENTRY + 8);
+        chip8.step();
+
+        assert!(!chip8.hw.vram_get(0, 0));
+        assert_eq!(chip8.reg(0xf).unwrap(), 1);
+    }
+
+    #[test]
+    fn shift_quirk_selects_vx_in_place_or_copy_from_vy() {
+        let rom = [0x80, 0x16]; // SHR V0, V1
+
+        let mut in_place = Chip8::with_quirks(
+            MockHw::new(),
+            Quirks {
+                shift_in_place: true,
+                ..Quirks::default()
+            },
+        );
+        in_place.init(&rom);
+        in_place.set_reg(0, 0b10).unwrap();
+        in_place.set_reg(1, 0b100).unwrap();
+        in_place.step();
+        assert_eq!(in_place.reg(0).unwrap(), 0b1);
+
+        let mut copy_vy = Chip8::with_quirks(
+            MockHw::new(),
+            Quirks {
+                shift_in_place: false,
+                ..Quirks::default()
+            },
+        );
+        copy_vy.init(&rom);
+        copy_vy.set_reg(0, 0b10).unwrap();
+        copy_vy.set_reg(1, 0b100).unwrap();
+        copy_vy.step();
+        assert_eq!(copy_vy.reg(0).unwrap(), 0b10);
+    }
+
+    #[test]
+    fn load_store_quirk_controls_whether_i_increments() {
+        let rom = [0xa3, 0x00, 0xf1, 0x55]; // LD I, 0x300 ; LD [I], V1
+
+        let mut keep = Chip8::with_quirks(
+            MockHw::new(),
+            Quirks {
+                load_store_increment: false,
+                ..Quirks::default()
+            },
+        );
+        keep.init(&rom);
+        keep.step();
+        keep.step();
+        assert_eq!(keep.i(), 0x300);
+
+        let mut inc = Chip8::with_quirks(
+            MockHw::new(),
+            Quirks {
+                load_store_increment: true,
+                ..Quirks::default()
+            },
+        );
+        inc.init(&rom);
+        inc.step();
+        inc.step();
+        assert_eq!(inc.i(), 0x302);
+    }
+
+    #[test]
+    fn jump_quirk_selects_v0_or_vx_as_jump_base() {
+        let rom = [0xb2, 0x10]; // JP V0/Vx, 0x210
+
+        let mut v0 = Chip8::with_quirks(
+            MockHw::new(),
+            Quirks {
+                jump_vx: false,
+                ..Quirks::default()
+            },
+        );
+        v0.init(&rom);
+        v0.set_reg(0, 0x10).unwrap();
+        v0.set_reg(2, 0xff).unwrap();
+        v0.step();
+        assert_eq!(v0.pc(), 0x210 + 0x10);
+
+        let mut vx = Chip8::with_quirks(
+            MockHw::new(),
+            Quirks {
+                jump_vx: true,
+                ..Quirks::default()
+            },
+        );
+        vx.init(&rom);
+        vx.set_reg(0, 0x10).unwrap();
+        vx.set_reg(2, 0x05).unwrap();
+        vx.step();
+        assert_eq!(vx.pc(), 0x210 + 0x05);
+    }
+
+    #[test]
+    fn vf_reset_quirk_controls_whether_or_and_xor_clear_vf() {
+        let rom = [0x80, 0x11]; // OR V0, V1
+
+        let mut keep = Chip8::with_quirks(
+            MockHw::new(),
+            Quirks {
+                vf_reset: false,
+                ..Quirks::default()
+            },
+        );
+        keep.init(&rom);
+        keep.set_reg(0xf, 1).unwrap();
+        keep.step();
+        assert_eq!(keep.reg(0xf).unwrap(), 1);
+
+        let mut reset = Chip8::with_quirks(
+            MockHw::new(),
+            Quirks {
+                vf_reset: true,
+                ..Quirks::default()
+            },
+        );
+        reset.init(&rom);
+        reset.set_reg(0xf, 1).unwrap();
+        reset.step();
+        assert_eq!(reset.reg(0xf).unwrap(), 0);
+    }
+
+    #[test]
+    fn display_wait_quirk_blocks_drw_until_next_tick() {
+        let rom = [
+            0x60, 0x00, // LD V0, 0
+            0x61, 0x00, // LD V1, 0
+            0xd0, 0x11, // DRW V0, V1, 1
+        ];
+
+        let mut no_wait = Chip8::with_quirks(
+            MockHw::new(),
+            Quirks {
+                display_wait: false,
+                ..Quirks::default()
+            },
+        );
+        no_wait.init(&rom);
+        no_wait.step();
+        no_wait.step();
+        let before = no_wait.hw.clock_calls;
+        no_wait.step(); // DRW: sched() runs once, like any other instruction
+        assert_eq!(no_wait.hw.clock_calls - before, 1);
+
+        let mut waits = Chip8::with_quirks(
+            MockHw::new(),
+            Quirks {
+                display_wait: true,
+                ..Quirks::default()
+            },
+        );
+        waits.init(&rom);
+        waits.step();
+        waits.step();
+        waits.hw.ticking = true;
+        let before = waits.hw.clock_calls;
+        waits.step(); // DRW: blocks in `wait_vblank`, costing extra sched() calls
+        assert!(waits.hw.clock_calls - before > 1);
+    }
+
+    #[test]
+    fn sound_on_fires_once_when_st_becomes_nonzero() {
+        let rom = [0x60, 0x02, 0xf0, 0x18]; // LD V0, 2 ; LD ST, V0
+        let mut chip8 = Chip8::new(MockHw::new());
+        chip8.init(&rom);
+        chip8.step();
+        chip8.step();
+
+        assert_eq!(chip8.st, 2);
+        assert_eq!(chip8.hw.sound_on_count, 1);
+        assert_eq!(chip8.hw.sound_off_count, 0);
+    }
+
+    #[test]
+    fn sound_off_fires_once_when_tick_decrements_st_to_zero() {
+        let mut chip8 = Chip8::new(MockHw::new());
+        chip8.init(&[]);
+        chip8.st = 1;
+        chip8.sound_playing = true;
+
+        chip8.tick();
+        assert_eq!(chip8.st, 0);
+        assert_eq!(chip8.hw.sound_off_count, 1);
+
+        // Further ticks with `st` already at 0 must not refire `sound_off`.
+        chip8.tick();
+        assert_eq!(chip8.hw.sound_off_count, 1);
+    }
+
+    #[test]
+    fn exec_frame_runs_configured_instruction_count_per_frame() {
+        let rom = [
+            0x60, 0x01, // LD V0, 1
+            0x60, 0x02, // LD V0, 2
+            0x60, 0x03, // LD V0, 3
+            0x60, 0x04, // LD V0, 4
+        ];
+        let mut chip8 = Chip8::new(MockHw::new());
+        chip8.init(&rom);
+        chip8.set_cycles_per_frame(2);
+
+        chip8.exec_frame();
+        assert_eq!(chip8.reg(0).unwrap(), 2);
+        assert_eq!(chip8.pc(), ENTRY + 4);
+
+        chip8.exec_frame();
+        assert_eq!(chip8.reg(0).unwrap(), 4);
+        assert_eq!(chip8.pc(), ENTRY + 8);
+    }
+
+    #[test]
+    fn exec_frame_stops_early_on_shutdown_mid_frame() {
+        let rom = [
+            0x60, 0x07, // LD V0, 7
+            0x00, 0xfd, // EXIT
+            0x60, 0x09, // LD V0, 9 (unreached)
+        ];
+        let mut chip8 = Chip8::new(MockHw::new());
+        chip8.init(&rom);
+        chip8.set_cycles_per_frame(10);
+
+        chip8.exec_frame();
+
+        assert_eq!(chip8.reg(0).unwrap(), 7);
+        assert!(!chip8.running);
+    }
 }